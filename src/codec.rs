@@ -0,0 +1,67 @@
+use anyhow::Context;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use clap::ValueEnum;
+use flate2::read::{MultiGzDecoder, ZlibDecoder};
+use lapin::message::Delivery;
+use std::borrow::Cow;
+use std::io::Read;
+
+/// How message bodies are encoded when writing them as raw output.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BodyEncoding {
+    /// Write the body as is
+    Raw,
+    /// Write the body base64 encoded
+    Base64,
+    /// Write the body as lowercase hexadecimal
+    Hex,
+}
+
+/// Encodes the body so binary data can safely be written to a terminal.
+pub fn encode_body(body: Cow<'_, [u8]>, encoding: BodyEncoding) -> Cow<'_, [u8]> {
+    match encoding {
+        BodyEncoding::Raw => body,
+        BodyEncoding::Base64 => Cow::Owned(STANDARD.encode(body).into_bytes()),
+        BodyEncoding::Hex => Cow::Owned(
+            body.iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect::<String>()
+                .into_bytes(),
+        ),
+    }
+}
+
+/// Decompresses the body according to the `content_encoding` property of the delivery. Bodies
+/// without a content encoding, or with one that isn't supported, are returned unchanged with a
+/// warning.
+pub fn decompress_body(delivery: &Delivery) -> anyhow::Result<Cow<'_, [u8]>> {
+    let encoding = delivery
+        .properties
+        .content_encoding()
+        .as_ref()
+        .map(|encoding| encoding.as_str().to_ascii_lowercase());
+
+    let mut body = Vec::new();
+    match encoding.as_deref() {
+        Some("gzip") => MultiGzDecoder::new(delivery.data.as_slice()).read_to_end(&mut body),
+        Some("deflate") => ZlibDecoder::new(delivery.data.as_slice()).read_to_end(&mut body),
+        Some(encoding) => {
+            eprintln!(
+                "warning: message {} has unsupported content encoding {encoding}, writing it unchanged",
+                delivery.delivery_tag
+            );
+            return Ok(Cow::Borrowed(&delivery.data));
+        }
+        None => {
+            eprintln!(
+                "warning: message {} has no content encoding, writing it unchanged",
+                delivery.delivery_tag
+            );
+            return Ok(Cow::Borrowed(&delivery.data));
+        }
+    }
+    .with_context(|| format!("failed to decompress message {}", delivery.delivery_tag))?;
+
+    Ok(Cow::Owned(body))
+}
@@ -0,0 +1,160 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use lapin::BasicProperties;
+use lapin::message::Delivery;
+use lapin::types::{AMQPValue, FieldTable};
+use serde_json::{Map, Value, json};
+
+/// Converts a delivery into a JSON object containing the body, routing information and the
+/// message properties. The body is passed separately so it can be decoded first.
+pub fn delivery_to_json(delivery: &Delivery, body: &[u8]) -> Value {
+    let (body, body_encoding) = body_to_json(body);
+
+    json!({
+        "delivery_tag": delivery.delivery_tag,
+        "exchange": delivery.exchange.as_str(),
+        "routing_key": delivery.routing_key.as_str(),
+        "redelivered": delivery.redelivered,
+        "properties": properties_to_json(&delivery.properties),
+        "body": body,
+        "body_encoding": body_encoding,
+    })
+}
+
+/// Returns the body as a UTF-8 string if possible and as base64 otherwise, together with the
+/// name of the encoding that was used.
+fn body_to_json(data: &[u8]) -> (Value, &'static str) {
+    match std::str::from_utf8(data) {
+        Ok(body) => (Value::String(body.to_string()), "utf8"),
+        Err(_) => (Value::String(STANDARD.encode(data)), "base64"),
+    }
+}
+
+/// Reformats the body as indented JSON, returns `None` when the body isn't valid JSON.
+pub fn pretty_json(body: &[u8]) -> Option<Vec<u8>> {
+    let value = serde_json::from_slice::<Value>(body).ok()?;
+    serde_json::to_vec_pretty(&value).ok()
+}
+
+/// Renders the message properties as human readable `key: value` lines, headers are listed
+/// indented below a `headers:` line.
+pub fn properties_to_text(properties: &BasicProperties) -> String {
+    let mut text = String::new();
+    if let Value::Object(map) = properties_to_json(properties) {
+        write_text_lines(&mut text, &map, 0);
+    }
+    text
+}
+
+fn write_text_lines(text: &mut String, map: &Map<String, Value>, indent: usize) {
+    for (key, value) in map {
+        let padding = "  ".repeat(indent);
+        match value {
+            Value::Object(map) => {
+                text.push_str(&format!("{padding}{key}:\n"));
+                write_text_lines(text, map, indent + 1);
+            }
+            Value::String(value) => text.push_str(&format!("{padding}{key}: {value}\n")),
+            value => text.push_str(&format!("{padding}{key}: {value}\n")),
+        }
+    }
+}
+
+fn properties_to_json(properties: &BasicProperties) -> Value {
+    let mut map = Map::new();
+
+    let short_strings = [
+        ("content_type", properties.content_type()),
+        ("content_encoding", properties.content_encoding()),
+        ("correlation_id", properties.correlation_id()),
+        ("reply_to", properties.reply_to()),
+        ("expiration", properties.expiration()),
+        ("message_id", properties.message_id()),
+        ("type", properties.kind()),
+        ("user_id", properties.user_id()),
+        ("app_id", properties.app_id()),
+        ("cluster_id", properties.cluster_id()),
+    ];
+
+    for (key, value) in short_strings {
+        if let Some(value) = value {
+            map.insert(key.to_string(), Value::from(value.as_str()));
+        }
+    }
+
+    if let Some(delivery_mode) = properties.delivery_mode() {
+        map.insert("delivery_mode".to_string(), Value::from(*delivery_mode));
+    }
+    if let Some(priority) = properties.priority() {
+        map.insert("priority".to_string(), Value::from(*priority));
+    }
+    if let Some(timestamp) = properties.timestamp() {
+        map.insert("timestamp".to_string(), Value::from(*timestamp));
+    }
+    if let Some(headers) = properties.headers() {
+        map.insert("headers".to_string(), field_table_to_json(headers));
+    }
+
+    Value::Object(map)
+}
+
+fn field_table_to_json(table: &FieldTable) -> Value {
+    Value::Object(
+        table
+            .inner()
+            .iter()
+            .map(|(key, value)| (key.to_string(), amqp_value_to_json(value)))
+            .collect(),
+    )
+}
+
+/// Converts a JSON value into an AMQP header value, strings are sent as long strings and nested
+/// values as tables and arrays.
+pub fn json_to_amqp_value(value: &Value) -> AMQPValue {
+    match value {
+        Value::Null => AMQPValue::Void,
+        Value::Bool(v) => AMQPValue::Boolean(*v),
+        Value::Number(v) => match v.as_i64() {
+            Some(v) => AMQPValue::LongLongInt(v),
+            None => AMQPValue::Double(v.as_f64().unwrap_or_default()),
+        },
+        Value::String(v) => AMQPValue::LongString(v.as_str().into()),
+        Value::Array(v) => {
+            AMQPValue::FieldArray(v.iter().map(json_to_amqp_value).collect::<Vec<_>>().into())
+        }
+        Value::Object(v) => {
+            let mut table = FieldTable::default();
+            for (key, value) in v {
+                table.insert(key.as_str().into(), json_to_amqp_value(value));
+            }
+            AMQPValue::FieldTable(table)
+        }
+    }
+}
+
+fn amqp_value_to_json(value: &AMQPValue) -> Value {
+    match value {
+        AMQPValue::Boolean(v) => Value::from(*v),
+        AMQPValue::ShortShortInt(v) => Value::from(*v),
+        AMQPValue::ShortShortUInt(v) => Value::from(*v),
+        AMQPValue::ShortInt(v) => Value::from(*v),
+        AMQPValue::ShortUInt(v) => Value::from(*v),
+        AMQPValue::LongInt(v) => Value::from(*v),
+        AMQPValue::LongUInt(v) => Value::from(*v),
+        AMQPValue::LongLongInt(v) => Value::from(*v),
+        AMQPValue::Float(v) => Value::from(*v),
+        AMQPValue::Double(v) => Value::from(*v),
+        AMQPValue::DecimalValue(v) => {
+            Value::from(f64::from(v.value) / 10f64.powi(i32::from(v.scale)))
+        }
+        AMQPValue::ShortString(v) => Value::from(v.as_str()),
+        AMQPValue::LongString(v) => body_to_json(v.as_bytes()).0,
+        AMQPValue::FieldArray(v) => {
+            Value::Array(v.as_slice().iter().map(amqp_value_to_json).collect())
+        }
+        AMQPValue::Timestamp(v) => Value::from(*v),
+        AMQPValue::FieldTable(v) => field_table_to_json(v),
+        AMQPValue::ByteArray(v) => Value::String(STANDARD.encode(v.as_slice())),
+        AMQPValue::Void => Value::Null,
+    }
+}
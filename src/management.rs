@@ -0,0 +1,84 @@
+use anyhow::Context;
+use lapin::uri::AMQPUri;
+use reqwest::Url;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// The port the RabbitMQ management plugin listens on by default.
+const DEFAULT_PORT: u16 = 15672;
+
+/// A client for the RabbitMQ management HTTP API.
+pub struct Client {
+    http: reqwest::Client,
+    base_url: Url,
+    username: String,
+    password: String,
+    vhost: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct QueueInfo {
+    pub name: String,
+    #[serde(default)]
+    pub messages: u64,
+    #[serde(default)]
+    pub consumers: u64,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct BindingInfo {
+    pub source: String,
+    pub routing_key: String,
+    #[serde(default)]
+    pub arguments: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Client {
+    /// Creates a client for the management API with the same credentials and vhost as the AMQP
+    /// URI. Without a management URL the API is expected on the default port of the same host.
+    pub fn new(uri: &AMQPUri, management_url: Option<&str>) -> anyhow::Result<Self> {
+        let management_url = match management_url {
+            Some(url) => url.trim_end_matches('/').to_string(),
+            None => format!("http://{}:{DEFAULT_PORT}", uri.authority.host),
+        };
+        let base_url = Url::parse(&format!("{management_url}/api/"))
+            .with_context(|| format!("invalid management URL {management_url}"))?;
+
+        Ok(Client {
+            http: reqwest::Client::new(),
+            base_url,
+            username: uri.authority.userinfo.username.clone(),
+            password: uri.authority.userinfo.password.clone(),
+            vhost: uri.vhost.clone(),
+        })
+    }
+
+    pub async fn list_queues(&self) -> anyhow::Result<Vec<QueueInfo>> {
+        self.get(&["queues", &self.vhost]).await
+    }
+
+    pub async fn list_bindings(&self, queue_name: &str) -> anyhow::Result<Vec<BindingInfo>> {
+        self.get(&["queues", &self.vhost, queue_name, "bindings"])
+            .await
+    }
+
+    async fn get<T: DeserializeOwned>(&self, segments: &[&str]) -> anyhow::Result<T> {
+        let mut url = self.base_url.clone();
+        url.path_segments_mut()
+            .map_err(|_| anyhow::anyhow!("invalid management URL"))?
+            .pop_if_empty()
+            .extend(segments);
+
+        self.http
+            .get(url.clone())
+            .basic_auth(&self.username, Some(&self.password))
+            .send()
+            .await
+            .with_context(|| format!("failed to request {url}"))?
+            .error_for_status()
+            .with_context(|| format!("management API request to {url} failed"))?
+            .json()
+            .await
+            .context("failed to parse management API response")
+    }
+}
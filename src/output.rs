@@ -0,0 +1,211 @@
+use crate::codec::{self, BodyEncoding};
+use crate::format;
+use anyhow::anyhow;
+use clap::ValueEnum;
+use lapin::message::Delivery;
+use std::borrow::Cow;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Write the message body as is
+    Raw,
+    /// Write a JSON object containing the body, routing information and properties
+    Json,
+}
+
+/// How `render_delivery` turns a delivery into the bytes that are written for it.
+pub struct RenderOptions<'a> {
+    pub format: OutputFormat,
+    pub encoding: BodyEncoding,
+    pub decompress: bool,
+    pub pretty: bool,
+    /// Prefixes raw output written to a shared stream, or adds a `queue` field to json output,
+    /// when reading several queues.
+    pub queue_name: Option<&'a str>,
+    /// Where the output is written to, `None` for stdout.
+    pub output: Option<&'a Path>,
+    pub append: bool,
+}
+
+/// Renders a delivery for the output, without the trailing newline. Errors only depend on the
+/// message itself, e.g. a body that cannot be decompressed.
+pub fn render_delivery<'a>(
+    delivery: &'a Delivery,
+    options: &RenderOptions,
+) -> anyhow::Result<Cow<'a, [u8]>> {
+    let mut body = if options.decompress {
+        codec::decompress_body(delivery)?
+    } else {
+        Cow::Borrowed(delivery.data.as_slice())
+    };
+    if options.pretty && options.format == OutputFormat::Raw {
+        match format::pretty_json(&body) {
+            Some(pretty) => body = Cow::Owned(pretty),
+            None => warn_invalid_json(delivery),
+        }
+    }
+
+    match options.format {
+        OutputFormat::Raw => {
+            let body = codec::encode_body(body, options.encoding);
+            let queue_name = options
+                .queue_name
+                .filter(|_| is_shared_output(options.output, options.append));
+            Ok(match queue_name {
+                Some(queue_name) => {
+                    Cow::Owned([format!("{queue_name}: ").as_bytes(), &body].concat())
+                }
+                None => body,
+            })
+        }
+        OutputFormat::Json => {
+            let mut value = format::delivery_to_json(delivery, &body);
+            if options.pretty {
+                match serde_json::from_slice::<serde_json::Value>(&body) {
+                    Ok(body) => {
+                        value["body"] = body;
+                        value["body_encoding"] = "json".into();
+                    }
+                    Err(_) => warn_invalid_json(delivery),
+                }
+            }
+            if let Some(queue_name) = options.queue_name {
+                value["queue"] = serde_json::Value::from(queue_name);
+            }
+            Ok(Cow::Owned(serde_json::to_vec(&value)?))
+        }
+    }
+}
+
+fn warn_invalid_json(delivery: &Delivery) {
+    eprintln!(
+        "warning: message {} is not valid JSON, writing it unchanged",
+        delivery.delivery_tag
+    );
+}
+
+/// Writes a rendered message followed by a newline and flushes the output, so the message can be
+/// acknowledged afterwards.
+pub fn write_message(output: &mut dyn Write, data: &[u8]) -> std::io::Result<()> {
+    output.write_all(data)?;
+    output.write_all(b"\n")?;
+    output.flush()
+}
+
+/// Checks that every message that is read can be written to the output. Stdout, a directory, a
+/// `{n}` template and an appended file can hold any number of messages, but every message would
+/// overwrite the previous one in a single file.
+pub fn check_output(path: Option<&Path>, append: bool, single_message: bool) -> anyhow::Result<()> {
+    match path {
+        Some(path) if !is_file_per_message(path) && !append && !single_message => Err(anyhow!(
+            "{} is not a directory, use a directory (ending with /), a {{n}} template, --append or --limit 1 to read multiple messages",
+            path.display()
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Whether the messages are written one after another to stdout or an appended file, as opposed to a
+/// file holding a single message where a prefix would become part of the body.
+pub fn is_shared_output(path: Option<&Path>, append: bool) -> bool {
+    match path {
+        Some(path) => append && !is_file_per_message(path),
+        None => true,
+    }
+}
+
+pub fn is_output_dir(path: &Path) -> bool {
+    path.is_dir() || path.to_string_lossy().ends_with("/")
+}
+
+/// A path containing `{n}` is a template for a file per message, `{n}` is replaced by the number of
+/// the message.
+pub fn is_output_template(path: &Path) -> bool {
+    path.to_string_lossy().contains("{n}")
+}
+
+/// Whether every message is written to its own file instead of to a single file.
+pub fn is_file_per_message(path: &Path) -> bool {
+    is_output_template(path) || is_output_dir(path)
+}
+
+pub fn open_output_file<D: Display>(path: &Path, offset: D) -> std::io::Result<File> {
+    if is_output_template(path) {
+        File::create(path.to_string_lossy().replace("{n}", &offset.to_string()))
+    } else if is_output_dir(path) {
+        std::fs::create_dir_all(path)?;
+        let file_path = path.join(format!("message_{offset}"));
+        File::create(file_path)
+    } else {
+        File::create(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stdout_holds_any_number_of_messages() {
+        assert!(check_output(None, false, false).is_ok());
+    }
+
+    #[test]
+    fn single_file_only_holds_a_single_message() {
+        let path = Path::new("out.txt");
+        assert!(check_output(Some(path), false, false).is_err());
+        assert!(check_output(Some(path), false, true).is_ok());
+        assert!(check_output(Some(path), true, false).is_ok());
+    }
+
+    #[test]
+    fn directory_holds_any_number_of_messages() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(check_output(Some(dir.path()), false, false).is_ok());
+        assert!(check_output(Some(Path::new("not-created-yet/")), false, false).is_ok());
+    }
+
+    #[test]
+    fn template_holds_any_number_of_messages() {
+        assert!(check_output(Some(Path::new("message-{n}.json")), false, false).is_ok());
+    }
+
+    #[test]
+    fn only_shared_output_is_prefixed_with_the_queue_name() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(is_shared_output(None, false));
+        assert!(is_shared_output(Some(Path::new("out.txt")), true));
+        assert!(!is_shared_output(Some(Path::new("out.txt")), false));
+        assert!(!is_shared_output(Some(dir.path()), false));
+        assert!(!is_shared_output(Some(dir.path()), true));
+        assert!(!is_shared_output(Some(Path::new("out/")), true));
+        assert!(!is_shared_output(Some(Path::new("message-{n}.json")), true));
+    }
+
+    #[test]
+    fn existing_file_is_overwritten() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.txt");
+
+        write_message(
+            &mut open_output_file(&path, 0).unwrap(),
+            b"a much longer first message",
+        )
+        .unwrap();
+        write_message(&mut open_output_file(&path, 1).unwrap(), b"second").unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "second\n");
+    }
+
+    #[test]
+    fn template_is_filled_in_with_the_message_number() {
+        let dir = tempfile::tempdir().unwrap();
+        let template = dir.path().join("message-{n}.txt");
+        open_output_file(&template, 7).unwrap();
+        assert!(dir.path().join("message-7.txt").exists());
+    }
+}